@@ -2,6 +2,7 @@ use std::{
     collections::HashMap,
     fs::File,
     io::{self, Read},
+    ops::Range,
     path::PathBuf,
 };
 
@@ -14,7 +15,7 @@ use rayon::prelude::*;
 use crate::LanguageType;
 
 /// Type of Line
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum LineType {
     /// Blank line
     Blank,
@@ -22,6 +23,8 @@ pub enum LineType {
     Code,
     /// Comment line
     Comment,
+    /// Documentation comment line (e.g. `///`, `//!`, `/** */`, `/*! */`)
+    DocComment,
 }
 
 impl LanguageType {
@@ -84,11 +87,13 @@ impl LanguageType {
             let (skippable_text, rest) = text.split_at(end + 1);
             let lines = LineIter::new(b'\n', skippable_text);
             let is_fortran = syntax.shared.is_fortran;
-            let comments = syntax.shared.line_comments;
+            let doc_comments = syntax.shared.doc_line_comments;
+            let any_comments = syntax.shared.any_comments.clone();
 
             let (mut annots_first, annots_last) = rayon::join(
                 move || {
                     self.annotate_lines(config, LineIter::new(b'\n', rest), annotations, syntax)
+                        .0
                 },
                 move || {
                     lines
@@ -104,7 +109,14 @@ impl LanguageType {
 
                             if line.trim().is_empty() {
                                 line_map.insert(num, LineType::Blank);
-                            } else if comments.iter().any(|c| line.starts_with(c.as_bytes())) {
+                            } else if doc_comments.iter().any(|c| line.starts_with(c.as_bytes())) {
+                                line_map.insert(num, LineType::DocComment);
+                            } else if any_comments.is_match(line) {
+                                // `important_syntax` doesn't include `"//"`, so this prefix can
+                                // contain a line whose comment marker isn't at the start (e.g. a
+                                // trailing `// note` after code); match it the same way the
+                                // window-loop path does instead of only checking the start of
+                                // the line.
                                 line_map.insert(num, LineType::Comment);
                             } else {
                                 line_map.insert(num, LineType::Code);
@@ -125,10 +137,356 @@ impl LanguageType {
             annots_first.extend(annots_last);
             annots_first
         } else {
-            self.annotate_lines(config, lines, annotations, syntax)
+            self.annotate_lines(config, lines, annotations, syntax).0
         }
     }
 
+    /// Like [`annotate_from_slice`](Self::annotate_from_slice), but splits large inputs into
+    /// line-aligned chunks and annotates each chunk in parallel, under the optimistic
+    /// assumption that it begins outside any multi-line comment or string — true for the
+    /// overwhelming majority of chunks. A cheap sequential reconciliation pass then walks the
+    /// chunks in order, carrying the real `SyntaxCounter` state across boundaries, and
+    /// re-annotates only the rare chunk whose assumed entry state turned out to be wrong (i.e.
+    /// the previous chunk actually ended inside a multi-line comment or quote).
+    ///
+    /// Falls back to `annotate_from_slice` for inputs too small to be worth chunking.
+    pub fn annotate_parallel_from_slice<A: AsRef<[u8]>>(
+        self,
+        text: A,
+        config: &Config,
+    ) -> HashMap<usize, LineType> {
+        self.annotate_parallel_from_slice_with_chunks(text, config, rayon::current_num_threads())
+    }
+
+    /// The actual implementation behind
+    /// [`annotate_parallel_from_slice`](Self::annotate_parallel_from_slice), parametrized over
+    /// the target chunk count instead of always reading it from
+    /// `rayon::current_num_threads()`. Split out so tests can pin a chunk count deterministically
+    /// rather than depending on the ambient thread count, which may be 1 on a single-core runner
+    /// and would otherwise make the chunk-boundary reconciliation path untestable.
+    fn annotate_parallel_from_slice_with_chunks<A: AsRef<[u8]>>(
+        self,
+        text: A,
+        config: &Config,
+        target_chunks: usize,
+    ) -> HashMap<usize, LineType> {
+        let text = text.as_ref();
+
+        if self.is_blank() {
+            return self.annotate_from_slice(text, config);
+        }
+
+        let chunks = line_aligned_chunks(text, target_chunks);
+
+        if chunks.len() <= 1 {
+            return self.annotate_from_slice(text, config);
+        }
+
+        // Optimistic parallel pass: every chunk is annotated as though it starts in plain mode.
+        let optimistic: Vec<(HashMap<usize, LineType>, SyntaxCounter)> = chunks
+            .par_iter()
+            .map(|&chunk| {
+                let syntax = SyntaxCounter::new(self);
+                self.annotate_lines(config, LineIter::new(b'\n', chunk), HashMap::new(), syntax)
+            })
+            .collect();
+
+        // Sequential reconciliation: carry the real state across chunk boundaries, offsetting
+        // each chunk's line numbers by the cumulative line count of preceding chunks.
+        let mut merged = HashMap::with_capacity(optimistic.iter().map(|(a, _)| a.len()).sum());
+        let mut line_offset = 0;
+        let mut carried_syntax = SyntaxCounter::new(self);
+
+        for (&chunk, (optimistic_annotations, ending_syntax)) in chunks.iter().zip(optimistic) {
+            let chunk_line_count = LineIter::new(b'\n', chunk).count();
+
+            let (annotations, ending_syntax) = if carried_syntax.is_plain_mode()
+                && !carried_syntax.in_fenced_block
+            {
+                // The optimistic "starts in plain mode" assumption held, so the parallel result
+                // for this chunk is already correct.
+                (optimistic_annotations, ending_syntax)
+            } else {
+                // The previous chunk actually ended mid multi-line comment/string, or inside a
+                // fenced code block within a run of line doc comments (which never touches
+                // `quote`/`stack`), so this chunk's optimistic guess was wrong; re-annotate it
+                // with the real carried state.
+                self.annotate_lines(
+                    config,
+                    LineIter::new(b'\n', chunk),
+                    HashMap::new(),
+                    carried_syntax,
+                )
+            };
+
+            for (line_num, line_type) in annotations {
+                merged.insert(line_offset + line_num, line_type);
+            }
+
+            carried_syntax = ending_syntax;
+            line_offset += chunk_line_count;
+        }
+
+        merged
+    }
+
+    /// Parses the text provided, returning column-accurate spans within each line rather than
+    /// collapsing a line to a single `LineType`. Each `Range<usize>` is a byte offset local to
+    /// its line, so `let x = 5; // note` is reported as a `Code` span followed by a `Comment`
+    /// span instead of disappearing into a single `LineType::Code` for the whole line.
+    pub fn annotate_spans_from_slice<A: AsRef<[u8]>>(
+        self,
+        text: A,
+        config: &Config,
+    ) -> HashMap<usize, Vec<(Range<usize>, LineType)>> {
+        let text = text.as_ref();
+        let lines = LineIter::new(b'\n', text);
+        let syntax = SyntaxCounter::new(self);
+
+        self.annotate_line_spans(config, lines, HashMap::new(), syntax)
+    }
+
+    /// Post-processes [`annotate_from_slice`](Self::annotate_from_slice) into contiguous line
+    /// runs rather than a dense per-line map, coalescing consecutive lines of the same
+    /// `LineType` into a single `Range<usize>` (inclusive start line, exclusive end). Editor
+    /// integrations building folding ranges want this directly: a 40-line license header
+    /// becomes one `Comment` run instead of 40 map entries.
+    ///
+    /// When `min_lines` is `Some`, runs shorter than it are dropped and the remaining runs of
+    /// the same `LineType` that become neighbours are joined, so a single blank separator
+    /// inside a comment block doesn't fragment a multi-line comment region into two tiny runs.
+    /// Ordering is made deterministic by sorting on start line before merging.
+    pub fn annotate_ranges_from_slice<A: AsRef<[u8]>>(
+        self,
+        text: A,
+        config: &Config,
+        min_lines: Option<usize>,
+    ) -> Vec<(LineType, Range<usize>)> {
+        let annotations = self.annotate_from_slice(text, config);
+
+        let mut lines: Vec<(usize, LineType)> = annotations.into_iter().collect();
+        lines.sort_by_key(|&(line_num, _)| line_num);
+
+        let mut runs: Vec<(LineType, Range<usize>)> = Vec::new();
+
+        for (line_num, line_type) in lines {
+            match runs.last_mut() {
+                Some((last_type, range)) if *last_type == line_type && range.end == line_num => {
+                    range.end = line_num + 1;
+                }
+                _ => runs.push((line_type, line_num..line_num + 1)),
+            }
+        }
+
+        if let Some(min_lines) = min_lines {
+            runs.retain(|(_, range)| range.end - range.start >= min_lines);
+
+            let mut joined: Vec<(LineType, Range<usize>)> = Vec::with_capacity(runs.len());
+            for (line_type, range) in runs {
+                match joined.last_mut() {
+                    Some((last_type, last)) if *last_type == line_type => last.end = range.end,
+                    _ => joined.push((line_type, range)),
+                }
+            }
+            runs = joined;
+        }
+
+        runs
+    }
+
+    #[inline]
+    fn annotate_line_spans<'a>(
+        self,
+        _config: &Config,
+        lines: impl IntoIterator<Item = &'a [u8]>,
+        mut annotations: HashMap<usize, Vec<(Range<usize>, LineType)>>,
+        mut syntax: SyntaxCounter,
+    ) -> HashMap<usize, Vec<(Range<usize>, LineType)>> {
+        for (line_num, raw_line) in lines.into_iter().enumerate() {
+            // `LineIter` keeps each line's `\n` terminator; spans are relative to the line
+            // without it.
+            let raw_line = raw_line.strip_suffix(b"\n").unwrap_or(raw_line);
+            // FORTRAN has a rule where it only counts as a comment if it's the
+            // first character in the column, so removing starting whitespace
+            // could cause a miscount.
+            let line = if syntax.shared.is_fortran {
+                raw_line
+            } else {
+                raw_line.trim()
+            };
+
+            if line.trim().is_empty() {
+                annotations.insert(line_num, vec![(0..raw_line.len(), LineType::Blank)]);
+                continue;
+            }
+
+            let had_multi_line = !syntax.stack.is_empty();
+            let had_open_quote = syntax.quote.is_some();
+            let was_doc_quote = syntax.quote_is_doc_quote;
+
+            if syntax.is_plain_mode() && !syntax.shared.important_syntax.is_match(line) {
+                let is_doc_comment = syntax
+                    .shared
+                    .doc_line_comments
+                    .iter()
+                    .any(|c| line.starts_with(c.as_bytes()));
+                let is_comments = !is_doc_comment
+                    && syntax
+                        .shared
+                        .line_comments
+                        .iter()
+                        .any(|c| line.starts_with(c.as_bytes()));
+
+                // A single span only covers the whole line correctly when any comment marker
+                // present starts the line (or there's none at all); a marker appearing after
+                // code (e.g. `let x = 5; // note`) needs the window loop below to split it into
+                // a `Code` span followed by a `Comment` span.
+                if is_doc_comment || is_comments || !syntax.shared.any_comments.is_match(line) {
+                    let kind = if is_doc_comment {
+                        LineType::DocComment
+                    } else if is_comments {
+                        LineType::Comment
+                    } else {
+                        LineType::Code
+                    };
+                    annotations.insert(line_num, vec![(0..raw_line.len(), kind)]);
+                    continue;
+                }
+            }
+
+            let mut spans: Vec<(Range<usize>, LineType)> = Vec::new();
+            let mut seg_start = 0;
+            // A plain string/char quote (`shared.quotes`) carried over from a previous line is
+            // not a comment, so it keeps `Code` rather than `Comment`; only a true multi-line
+            // comment or a doc quote gets labeled as such.
+            let mut open_kind = if had_multi_line {
+                Some(LineType::Comment)
+            } else if had_open_quote {
+                Some(if was_doc_quote {
+                    LineType::DocComment
+                } else {
+                    LineType::Code
+                })
+            } else {
+                None
+            };
+
+            let mut skip = 0;
+            macro_rules! skip {
+                ($skip:expr) => {{
+                    skip = $skip - 1;
+                }};
+            }
+
+            'window: for i in 0..line.len() {
+                if skip != 0 {
+                    skip -= 1;
+                    continue;
+                }
+
+                let window = &line[i..];
+
+                let is_end_of_quote_or_multi_line = syntax
+                    .parse_end_of_quote(window)
+                    .or_else(|| syntax.parse_end_of_multi_line(window));
+
+                if let Some(skip_amount) = is_end_of_quote_or_multi_line {
+                    let end = i + skip_amount;
+                    let kind = open_kind.take().unwrap_or(LineType::Comment);
+                    spans.push((seg_start..end, kind));
+                    seg_start = end;
+                    skip!(skip_amount);
+                    continue;
+                } else if syntax.quote.is_some() {
+                    continue;
+                }
+
+                let is_quote_or_multi_line = syntax
+                    .parse_quote(window)
+                    .or_else(|| syntax.parse_multi_line_comment(window));
+
+                if let Some(skip_amount) = is_quote_or_multi_line {
+                    if seg_start < i {
+                        spans.push((seg_start..i, LineType::Code));
+                    }
+                    seg_start = i;
+                    open_kind = Some(if syntax.quote.is_some() {
+                        // Opened via `parse_quote`: a doc quote is a comment, but a plain
+                        // string/char quote is still `Code`.
+                        if syntax.quote_is_doc_quote {
+                            LineType::DocComment
+                        } else {
+                            LineType::Code
+                        }
+                    } else {
+                        // Opened via `parse_multi_line_comment` instead (`quote` stays `None`).
+                        LineType::Comment
+                    });
+                    skip!(skip_amount);
+                    continue;
+                }
+
+                if syntax.parse_line_comment(window) {
+                    if seg_start < i {
+                        spans.push((seg_start..i, LineType::Code));
+                    }
+                    let kind = if syntax
+                        .shared
+                        .doc_line_comments
+                        .iter()
+                        .any(|c| window.starts_with(c.as_bytes()))
+                    {
+                        LineType::DocComment
+                    } else {
+                        LineType::Comment
+                    };
+                    spans.push((i..line.len(), kind));
+                    seg_start = line.len();
+                    break 'window;
+                }
+            }
+
+            if let Some(kind) = open_kind {
+                if seg_start < line.len() {
+                    spans.push((seg_start..line.len(), kind));
+                }
+            } else if seg_start < line.len() {
+                spans.push((seg_start..line.len(), LineType::Code));
+            }
+
+            if spans.is_empty() {
+                spans.push((0..line.len(), LineType::Code));
+            }
+
+            // `line` is `raw_line` trimmed, so the spans above are relative to the trimmed
+            // slice, not `raw_line` as the API promises. Translate them back, folding any
+            // stripped leading/trailing whitespace into the first/last span so the spans cover
+            // `raw_line` exactly.
+            let leading_ws = if syntax.shared.is_fortran {
+                0
+            } else {
+                raw_line.len() - raw_line.trim_start().len()
+            };
+            let last = spans.len() - 1;
+            for (idx, (range, _)) in spans.iter_mut().enumerate() {
+                range.start = if idx == 0 { 0 } else { range.start + leading_ws };
+                range.end = if idx == last {
+                    raw_line.len()
+                } else {
+                    range.end + leading_ws
+                };
+            }
+
+            annotations.insert(line_num, spans);
+        }
+
+        annotations
+    }
+
+    /// Annotates `lines`, returning both the annotations and the final `SyntaxCounter` state,
+    /// so a caller juggling multiple chunks of the same file (see
+    /// [`annotate_parallel_from_slice`](Self::annotate_parallel_from_slice)) can carry that
+    /// state across a chunk boundary.
     #[inline]
     fn annotate_lines<'a>(
         self,
@@ -136,7 +494,7 @@ impl LanguageType {
         lines: impl IntoIterator<Item = &'a [u8]>,
         mut annotations: HashMap<usize, LineType>,
         mut syntax: SyntaxCounter,
-    ) -> HashMap<usize, LineType> {
+    ) -> (HashMap<usize, LineType>, SyntaxCounter) {
         for (line_num, line) in lines.into_iter().enumerate() {
             // FORTRAN has a rule where it only counts as a comment if it's the
             // first character in the column, so removing starting whitespace
@@ -155,18 +513,20 @@ impl LanguageType {
             } else if syntax.is_plain_mode() && !syntax.shared.important_syntax.is_match(line) {
                 trace!("^ Skippable");
 
-                if syntax
+                let is_doc_comment = syntax
                     .shared
-                    .line_comments
+                    .doc_line_comments
                     .iter()
-                    .any(|c| line.starts_with(c.as_bytes()))
-                {
-                    annotations.insert(line_num, LineType::Comment);
-                    trace!("Comment on Line No.{}", line_num);
-                } else {
-                    annotations.insert(line_num, LineType::Code);
-                    trace!("Code on Line No.{}", line_num);
-                }
+                    .any(|c| line.starts_with(c.as_bytes()));
+                // `any_comments` matches anywhere in the line (not just at the start), so a
+                // trailing `// note` after code is still reported as `Comment`, matching the
+                // slow path's `is_comments` below.
+                let is_comments = !is_doc_comment && syntax.shared.any_comments.is_match(line);
+
+                let line_type =
+                    classify_with_fences(&mut syntax, config, line, is_doc_comment, is_comments);
+                trace!("{:?} on Line No.{}", line_type, line_num);
+                annotations.insert(line_num, line_type);
                 continue;
             }
 
@@ -217,33 +577,307 @@ impl LanguageType {
 
             trace!("{}", String::from_utf8_lossy(line));
 
+            let is_doc_comment = ((
+                // If we're currently in a doc string or we just ended
+                // with one.
+                syntax.quote.is_some()
+                    || syntax
+                        .shared
+                        .doc_quotes
+                        .iter()
+                        .any(|(s, _)| line.starts_with(s.as_bytes()))
+            ) && syntax.quote_is_doc_quote)
+                || syntax
+                    .shared
+                    .doc_line_comments
+                    .iter()
+                    .any(|c| line.starts_with(c.as_bytes()));
+
             let is_comments = ((!syntax.stack.is_empty() || ended_with_comments) && had_multi_line)
                 || (
                     // If we're currently in a comment or we just ended
                     // with one.
                     syntax.shared.any_comments.is_match(line) && syntax.quote.is_none()
-                )
-                || ((
-                        // If we're currently in a doc string or we just ended
-                        // with one.
-                        syntax.quote.is_some() ||
-                        syntax.shared.doc_quotes.iter().any(|(s, _)| line.starts_with(s.as_bytes()))
-                    ) &&
-                    // `Some(true)` is import in order to respect the current
-                    // configuration.
-                    config.treat_doc_strings_as_comments == Some(true) &&
-                    syntax.quote_is_doc_quote);
-
-            if is_comments {
-                annotations.insert(line_num, LineType::Comment);
-                trace!("Comment on Line No.{}", line_num);
-                trace!("Was the Comment stack empty?: {}", !had_multi_line);
-            } else {
-                annotations.insert(line_num, LineType::Code);
-                trace!("Code on Line No.{}", line_num);
+                );
+
+            let line_type =
+                classify_with_fences(&mut syntax, config, line, is_doc_comment, is_comments);
+
+            match line_type {
+                LineType::DocComment => trace!("DocComment on Line No.{}", line_num),
+                LineType::Comment => {
+                    trace!("Comment on Line No.{}", line_num);
+                    trace!("Was the Comment stack empty?: {}", !had_multi_line);
+                }
+                LineType::Code => trace!("Code on Line No.{}", line_num),
+                LineType::Blank => unreachable!("blank lines are handled above"),
             }
+            annotations.insert(line_num, line_type);
         }
 
-        annotations
+        (annotations, syntax)
+    }
+}
+
+/// Splits `text` into at most `target_chunks` pieces, splitting only at `\n` boundaries so a
+/// chunk never cuts a line in half. Returns a single chunk (the whole input) if `text` is too
+/// small relative to `target_chunks` for splitting to be worthwhile.
+fn line_aligned_chunks(text: &[u8], target_chunks: usize) -> Vec<&[u8]> {
+    if target_chunks <= 1 || text.is_empty() {
+        return vec![text];
+    }
+
+    let approx_len = text.len() / target_chunks;
+
+    if approx_len == 0 {
+        return vec![text];
+    }
+
+    let mut chunks = Vec::with_capacity(target_chunks);
+    let mut start = 0;
+
+    while start < text.len() {
+        let tentative_end = (start + approx_len).min(text.len());
+        let end = if tentative_end >= text.len() {
+            text.len()
+        } else {
+            match text[tentative_end..].iter().position(|&b| b == b'\n') {
+                Some(offset) => tentative_end + offset + 1,
+                None => text.len(),
+            }
+        };
+
+        chunks.push(&text[start..end]);
+        start = end;
+    }
+
+    chunks
+}
+
+/// Classifies a non-blank line as `DocComment`/`Comment`/`Code` and, when
+/// `config.count_doc_code_as_code` is set, applies the fenced-code-block override on top: a
+/// fence delimiter line stays `Comment`, lines strictly inside a fence become `Code`, and
+/// leaving the doc comment resets the fence state. Shared by both the fast "skippable" path and
+/// the full character-by-character walk in `annotate_lines`, so fenced doc examples are counted
+/// consistently regardless of which path classified the line.
+fn classify_with_fences(
+    syntax: &mut SyntaxCounter,
+    config: &Config,
+    line: &[u8],
+    is_doc_comment: bool,
+    is_comments: bool,
+) -> LineType {
+    let mut line_type = if is_doc_comment {
+        LineType::DocComment
+    } else if is_comments {
+        LineType::Comment
+    } else {
+        LineType::Code
+    };
+
+    if config.count_doc_code_as_code == Some(true) {
+        if is_doc_comment {
+            let stripped = strip_doc_marker(syntax, line);
+
+            if let Some((fence_char, fence_len)) = fence_delimiter(stripped) {
+                if syntax.in_fenced_block {
+                    if fence_char == syntax.fence_char && fence_len >= syntax.fence_len {
+                        syntax.in_fenced_block = false;
+                    }
+                } else {
+                    syntax.in_fenced_block = true;
+                    syntax.fence_char = fence_char;
+                    syntax.fence_len = fence_len;
+                }
+                // The fence delimiter itself is still a comment line.
+                line_type = LineType::Comment;
+            } else if syntax.in_fenced_block {
+                line_type = LineType::Code;
+            }
+        } else {
+            // We've left the doc comment, so any fenced state it held is stale.
+            syntax.in_fenced_block = false;
+        }
+    }
+
+    line_type
+}
+
+/// Strips a doc comment's leading marker (`///`, `//!`, `/**`, `/*!`, ...) and, if present, a
+/// `*` continuation marker used by indented lines inside a block doc comment, so the remaining
+/// text can be checked for a code-fence delimiter.
+fn strip_doc_marker<'a>(syntax: &SyntaxCounter, line: &'a [u8]) -> &'a [u8] {
+    let mut rest = line;
+
+    for marker in syntax.shared.doc_line_comments.iter() {
+        if let Some(stripped) = rest.strip_prefix(marker.as_bytes()) {
+            rest = stripped;
+            break;
+        }
+    }
+
+    for (open, _) in syntax.shared.doc_quotes.iter() {
+        if let Some(stripped) = rest.strip_prefix(open.as_bytes()) {
+            rest = stripped;
+            break;
+        }
+    }
+
+    let rest = rest.trim();
+    rest.strip_prefix(b"*").map(|r| r.trim()).unwrap_or(rest)
+}
+
+/// Checks whether `line` opens or closes a fenced code block (three or more backticks or
+/// tildes), returning the fence character and its length.
+fn fence_delimiter(line: &[u8]) -> Option<(u8, usize)> {
+    let fence_char = *line.first()?;
+
+    if fence_char != b'`' && fence_char != b'~' {
+        return None;
+    }
+
+    let fence_len = line.iter().take_while(|&&b| b == fence_char).count();
+
+    if fence_len >= 3 {
+        Some((fence_char, fence_len))
+    } else {
+        None
+    }
+}
+
+/// Helpers for writing compact, inline regression fixtures against the annotation APIs,
+/// instead of checking in a real file and hand-counting line numbers.
+#[cfg(feature = "testing")]
+pub mod testing {
+    use super::{LineType, *};
+
+    /// A fixture's classifications didn't match what [`annotate_fixture`] expected.
+    ///
+    /// `Display`s as a two-column diff of source line vs. expected/actual `LineType`, so a
+    /// failing fixture is readable at a glance instead of a bare `assert_eq!` panic.
+    #[derive(Debug)]
+    pub struct Mismatch {
+        entries: Vec<(usize, String, LineType, LineType)>,
+    }
+
+    impl std::fmt::Display for Mismatch {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            writeln!(f, "{:>4}  {:<40}  expected    actual", "line", "source")?;
+            for (line_num, source, expected, actual) in &self.entries {
+                writeln!(
+                    f,
+                    "{:>4}  {:<40}  {:<10?}  {:?}",
+                    line_num + 1,
+                    source,
+                    expected,
+                    actual
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    impl std::error::Error for Mismatch {}
+
+    /// Parses `text` as `language` and checks that every line's [`LineType`] matches `codes`:
+    /// a string with one character per line of `text` (`B` blank, `C` comment, `D` doc comment,
+    /// `X` code). On success returns `Ok(())`; on any mismatch returns every mismatching line
+    /// as a [`Mismatch`], so contributors adding new language syntax can write compact
+    /// regression fixtures instead of hand-counting line numbers in a checked-in file.
+    ///
+    /// ```
+    /// # use tokei::{Config, LanguageType};
+    /// # use tokei::testing::annotate_fixture;
+    /// let source = "// comment\nlet x = 1;\n\n/// doc\n";
+    /// let codes = "CXBD";
+    /// annotate_fixture(LanguageType::Rust, source, codes, &Config::default()).unwrap();
+    /// ```
+    pub fn annotate_fixture(
+        language: LanguageType,
+        text: &str,
+        codes: &str,
+        config: &Config,
+    ) -> Result<(), Mismatch> {
+        let line_count = text.lines().count();
+        let code_count = codes.chars().count();
+        assert_eq!(
+            line_count, code_count,
+            "fixture `codes` has {} character(s) but `text` has {} line(s); they must be \
+             one-to-one",
+            code_count, line_count
+        );
+
+        let annotated = language.annotate_from_slice(text.as_bytes(), config);
+        let mut entries = Vec::new();
+
+        for (line_num, (source, code)) in text.lines().zip(codes.chars()).enumerate() {
+            let expected = match code {
+                'B' => LineType::Blank,
+                'C' => LineType::Comment,
+                'D' => LineType::DocComment,
+                'X' => LineType::Code,
+                other => panic!(
+                    "unknown fixture code `{}` on line {}, expected one of B/C/D/X",
+                    other,
+                    line_num + 1
+                ),
+            };
+            let actual = annotated.get(&line_num).copied().unwrap_or(LineType::Code);
+
+            if actual != expected {
+                entries.push((line_num, source.to_owned(), expected, actual));
+            }
+        }
+
+        if entries.is_empty() {
+            Ok(())
+        } else {
+            Err(Mismatch { entries })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_aligned_chunks_never_splits_mid_line() {
+        let text = b"aaa\nbbb\nccc\nddd\neee\n";
+
+        let chunks = line_aligned_chunks(text, 3);
+
+        assert!(chunks.len() >= 2, "expected the input to actually be split");
+        for chunk in &chunks {
+            assert!(
+                chunk.is_empty() || chunk.ends_with(b"\n"),
+                "a chunk boundary fell mid-line: {:?}",
+                String::from_utf8_lossy(chunk)
+            );
+        }
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[test]
+    fn parallel_reconciliation_matches_sequential_across_a_fenced_chunk_boundary() {
+        // A fenced code block inside a run of `///` doc comments, long enough that pinning the
+        // chunk count to 2 (instead of depending on `rayon::current_num_threads()`) reliably
+        // splits it mid-block, forcing the `in_fenced_block` reconciliation path to kick in.
+        let mut source = String::from("/// ```\n");
+        for i in 0..40 {
+            source.push_str(&format!("/// line {}\n", i));
+        }
+        source.push_str("/// ```\nfn foo() {}\n");
+
+        let config = Config {
+            count_doc_code_as_code: Some(true),
+            ..Config::default()
+        };
+
+        let sequential = LanguageType::Rust.annotate_from_slice(&source, &config);
+        let parallel =
+            LanguageType::Rust.annotate_parallel_from_slice_with_chunks(&source, &config, 2);
+
+        assert_eq!(parallel, sequential);
     }
 }
@@ -0,0 +1,309 @@
+use crate::LanguageType;
+
+/// A single literal-pattern match within a byte haystack, giving just enough of `regex::Match`'s
+/// surface for the annotation pass to locate the earliest important token.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PatternMatch {
+    start: usize,
+}
+
+impl PatternMatch {
+    pub(crate) fn start(&self) -> usize {
+        self.start
+    }
+}
+
+/// A small set of literal byte patterns, matched by naive substring search. Used for the
+/// `important_syntax`/`any_comments` matchers, which only ever need to know *whether* and
+/// *where* one of a handful of short markers (`"`, `/*`, `//`, ...) shows up.
+#[derive(Debug, Clone)]
+pub(crate) struct PatternSet {
+    patterns: &'static [&'static str],
+}
+
+impl PatternSet {
+    const fn new(patterns: &'static [&'static str]) -> Self {
+        Self { patterns }
+    }
+
+    pub(crate) fn is_match(&self, haystack: &[u8]) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| contains(haystack, pattern.as_bytes()))
+    }
+
+    pub(crate) fn earliest_find(&self, haystack: &[u8]) -> Option<PatternMatch> {
+        self.patterns
+            .iter()
+            .filter_map(|pattern| find(haystack, pattern.as_bytes()))
+            .min()
+            .map(|start| PatternMatch { start })
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return None;
+    }
+
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    find(haystack, needle).is_some()
+}
+
+/// The per-language syntax tables shared by every `SyntaxCounter` built for a given
+/// `LanguageType`. Everything here is `'static` data describing how that language opens and
+/// closes comments, strings, and doc comments.
+#[derive(Debug, Clone)]
+pub(crate) struct SharedMatchers {
+    /// FORTRAN only counts a comment if its marker is the first character in the column, so
+    /// leading whitespace must not be stripped before checking for one.
+    pub(crate) is_fortran: bool,
+    /// Ordinary single-line comment markers, e.g. `//`.
+    pub(crate) line_comments: &'static [&'static str],
+    /// Single-line documentation comment markers, e.g. `///`, `//!`.
+    pub(crate) doc_line_comments: &'static [&'static str],
+    /// Block documentation comment (open, close) pairs, e.g. `("/**", "*/")`.
+    pub(crate) doc_quotes: &'static [(&'static str, &'static str)],
+    /// String/char quote (open, close) pairs, e.g. `("\"", "\"")`.
+    pub(crate) quotes: &'static [(&'static str, &'static str)],
+    /// Ordinary block comment (open, close) pairs, e.g. `("/*", "*/")`.
+    pub(crate) multi_line_comments: &'static [(&'static str, &'static str)],
+    /// Matches any token that can change comment/quote state, used to skip the expensive
+    /// character-by-character walk for lines that plainly can't contain one.
+    pub(crate) important_syntax: PatternSet,
+    /// Matches any of this language's comment markers (line or block, doc or not).
+    pub(crate) any_comments: PatternSet,
+}
+
+impl SharedMatchers {
+    fn empty() -> Self {
+        Self {
+            is_fortran: false,
+            line_comments: &[],
+            doc_line_comments: &[],
+            doc_quotes: &[],
+            quotes: &[],
+            multi_line_comments: &[],
+            important_syntax: PatternSet::new(&[]),
+            any_comments: PatternSet::new(&[]),
+        }
+    }
+}
+
+/// Tracks the comment/quote state while walking a file line-by-line.
+#[derive(Debug, Clone)]
+pub(crate) struct SyntaxCounter {
+    pub(crate) shared: SharedMatchers,
+    /// Close markers of currently-open, possibly nested, block comments.
+    pub(crate) stack: Vec<&'static str>,
+    /// The close marker of a currently-open quote or block doc comment, if any.
+    pub(crate) quote: Option<&'static str>,
+    /// Whether the most recently opened `quote` is a doc comment/string rather than a plain
+    /// one. Kept after that quote closes, so a line that opens and closes a doc comment (e.g.
+    /// `/** doc */`) is still recognised as one.
+    pub(crate) quote_is_doc_quote: bool,
+    /// Whether we're currently inside a fenced (``` or ~~~) code block embedded in a doc
+    /// comment, when `count_doc_code_as_code` is enabled. Persists across lines within one doc
+    /// comment block and is reset once that block closes.
+    pub(crate) in_fenced_block: bool,
+    /// The fence character (`` ` `` or `~`) that opened the current fenced block.
+    pub(crate) fence_char: u8,
+    /// The length of the fence that opened the current fenced block.
+    pub(crate) fence_len: usize,
+}
+
+impl SyntaxCounter {
+    pub(crate) fn new(language: LanguageType) -> Self {
+        Self {
+            shared: shared_matchers(language),
+            stack: Vec::new(),
+            quote: None,
+            quote_is_doc_quote: false,
+            in_fenced_block: false,
+            fence_char: b'`',
+            fence_len: 0,
+        }
+    }
+
+    pub(crate) fn is_plain_mode(&self) -> bool {
+        self.stack.is_empty() && self.quote.is_none()
+    }
+
+    /// If `window` opens a string/char quote or a block doc comment, records its close marker
+    /// and returns the number of bytes to skip.
+    pub(crate) fn parse_quote(&mut self, window: &[u8]) -> Option<usize> {
+        if self.quote.is_some() {
+            return None;
+        }
+
+        for (open, close) in self.shared.doc_quotes.iter() {
+            if window.starts_with(open.as_bytes()) {
+                self.quote = Some(close);
+                self.quote_is_doc_quote = true;
+                return Some(open.len());
+            }
+        }
+
+        for (open, close) in self.shared.quotes.iter() {
+            if window.starts_with(open.as_bytes()) {
+                self.quote = Some(close);
+                self.quote_is_doc_quote = false;
+                return Some(open.len());
+            }
+        }
+
+        None
+    }
+
+    /// If a `quote` is open and `window` starts with its close marker, clears it and returns
+    /// the number of bytes to skip.
+    pub(crate) fn parse_end_of_quote(&mut self, window: &[u8]) -> Option<usize> {
+        let close = self.quote?;
+
+        if window.starts_with(close.as_bytes()) {
+            self.quote = None;
+            Some(close.len())
+        } else {
+            None
+        }
+    }
+
+    /// If `window` opens an ordinary (non-doc) block comment, pushes its close marker onto the
+    /// stack and returns the number of bytes to skip.
+    pub(crate) fn parse_multi_line_comment(&mut self, window: &[u8]) -> Option<usize> {
+        for (open, close) in self.shared.multi_line_comments.iter() {
+            if window.starts_with(open.as_bytes()) {
+                self.stack.push(close);
+                return Some(open.len());
+            }
+        }
+
+        None
+    }
+
+    /// If a block comment is open and `window` starts with its close marker, pops the stack and
+    /// returns the number of bytes to skip.
+    pub(crate) fn parse_end_of_multi_line(&mut self, window: &[u8]) -> Option<usize> {
+        let close = *self.stack.last()?;
+
+        if window.starts_with(close.as_bytes()) {
+            self.stack.pop();
+            Some(close.len())
+        } else {
+            None
+        }
+    }
+
+    /// Whether `window` starts a line comment, ordinary or doc.
+    pub(crate) fn parse_line_comment(&self, window: &[u8]) -> bool {
+        self.shared
+            .line_comments
+            .iter()
+            .chain(self.shared.doc_line_comments.iter())
+            .any(|marker| window.starts_with(marker.as_bytes()))
+    }
+}
+
+fn shared_matchers(language: LanguageType) -> SharedMatchers {
+    match language {
+        LanguageType::Rust => SharedMatchers {
+            is_fortran: false,
+            line_comments: &["//"],
+            doc_line_comments: &["///", "//!"],
+            doc_quotes: &[("/**", "*/"), ("/*!", "*/")],
+            quotes: &[("\"", "\"")],
+            multi_line_comments: &[("/*", "*/")],
+            // `"//"` is deliberately left out here (it's still in `any_comments`): keeping it
+            // would make every line containing a line comment, anywhere, take the slow
+            // character-by-character path in `annotate_lines`, leaving the fast "skippable" path
+            // (and the doc-comment prefix check it does) dead code.
+            important_syntax: PatternSet::new(&["\"", "/*"]),
+            any_comments: PatternSet::new(&["//", "/*"]),
+        },
+        LanguageType::C | LanguageType::Cpp | LanguageType::CSharp => SharedMatchers {
+            is_fortran: false,
+            line_comments: &["//"],
+            doc_line_comments: &["///", "//!"],
+            doc_quotes: &[("/**", "*/")],
+            quotes: &[("\"", "\"")],
+            multi_line_comments: &[("/*", "*/")],
+            important_syntax: PatternSet::new(&["\"", "/*"]),
+            any_comments: PatternSet::new(&["//", "/*"]),
+        },
+        LanguageType::Go => SharedMatchers {
+            is_fortran: false,
+            line_comments: &["//"],
+            doc_line_comments: &[],
+            doc_quotes: &[],
+            quotes: &[("\"", "\""), ("`", "`")],
+            multi_line_comments: &[("/*", "*/")],
+            important_syntax: PatternSet::new(&["\"", "`", "/*"]),
+            any_comments: PatternSet::new(&["//", "/*"]),
+        },
+        LanguageType::Java | LanguageType::JavaScript | LanguageType::TypeScript => {
+            SharedMatchers {
+                is_fortran: false,
+                line_comments: &["//"],
+                doc_line_comments: &[],
+                doc_quotes: &[("/**", "*/")],
+                quotes: &[("\"", "\""), ("'", "'")],
+                multi_line_comments: &[("/*", "*/")],
+                important_syntax: PatternSet::new(&["\"", "'", "/*"]),
+                any_comments: PatternSet::new(&["//", "/*"]),
+            }
+        }
+        LanguageType::Python => SharedMatchers {
+            is_fortran: false,
+            line_comments: &["#"],
+            doc_line_comments: &[],
+            doc_quotes: &[],
+            quotes: &[("\"", "\""), ("'", "'")],
+            multi_line_comments: &[("\"\"\"", "\"\"\""), ("'''", "'''")],
+            important_syntax: PatternSet::new(&["\"", "'", "#"]),
+            any_comments: PatternSet::new(&["#"]),
+        },
+        LanguageType::Ruby | LanguageType::Sh => SharedMatchers {
+            is_fortran: false,
+            line_comments: &["#"],
+            doc_line_comments: &[],
+            doc_quotes: &[],
+            quotes: &[("\"", "\""), ("'", "'")],
+            multi_line_comments: &[],
+            important_syntax: PatternSet::new(&["\"", "'", "#"]),
+            any_comments: PatternSet::new(&["#"]),
+        },
+        // `is_fortran` makes callers skip trimming leading whitespace before checking for a
+        // comment marker. `any_comments` still matches `!` anywhere in the line, so an indented
+        // or trailing `!` comment is counted as a comment too.
+        LanguageType::FortranModern => SharedMatchers {
+            is_fortran: true,
+            line_comments: &["!"],
+            doc_line_comments: &[],
+            doc_quotes: &[],
+            quotes: &[("\"", "\""), ("'", "'")],
+            multi_line_comments: &[],
+            important_syntax: PatternSet::new(&["\"", "'", "!"]),
+            any_comments: PatternSet::new(&["!"]),
+        },
+        LanguageType::FortranLegacy => SharedMatchers {
+            is_fortran: true,
+            // Fixed-form FORTRAN's `C`/`c`/`*` column-1 comment markers aren't modeled here:
+            // unlike `!`, they're ordinary letters/characters that would also match inside
+            // unrelated code if used with `any_comments`' substring search, since that search
+            // isn't column-aware. Only the unambiguous `!` marker (also accepted by most
+            // compilers as a fixed-form extension) is matched; `is_fortran` still disables
+            // leading-whitespace trimming so a future column-aware matcher can be added here.
+            line_comments: &["!"],
+            doc_line_comments: &[],
+            doc_quotes: &[],
+            quotes: &[("\"", "\""), ("'", "'")],
+            multi_line_comments: &[],
+            important_syntax: PatternSet::new(&["\"", "'", "!"]),
+            any_comments: PatternSet::new(&["!"]),
+        },
+        _ => SharedMatchers::empty(),
+    }
+}
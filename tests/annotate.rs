@@ -22,4 +22,174 @@ mod annotate {
         assert_eq!(annotated.get(&3).unwrap(), &LineType::Blank);
         assert_eq!(annotated.get(&5).unwrap(), &LineType::Blank);
     }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn annotate_fixture_matches_codes() {
+        use tokei::testing::annotate_fixture;
+
+        let source = "// comment\nlet x = 1;\n\n/// doc\n";
+        let codes = "CXBD";
+
+        annotate_fixture(LanguageType::Rust, source, codes, &Config::default()).unwrap();
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    #[should_panic(expected = "one-to-one")]
+    fn annotate_fixture_rejects_mismatched_codes_length() {
+        use tokei::testing::annotate_fixture;
+
+        let source = "// comment\nlet x = 1;\n";
+        let codes = "C";
+
+        let _ = annotate_fixture(LanguageType::Rust, source, codes, &Config::default());
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn annotate_fixture_trailing_comment_is_still_a_comment_line() {
+        use tokei::testing::annotate_fixture;
+
+        // `important_syntax` deliberately doesn't include `"//"`, so this line takes the fast
+        // "skippable" path in `annotate_lines`; it must still come out `Comment`, same as a line
+        // whose `//` forces the slow window-loop path.
+        let source = "let x = 1; // note\n";
+        let codes = "C";
+
+        annotate_fixture(LanguageType::Rust, source, codes, &Config::default()).unwrap();
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn annotate_fixture_trailing_comment_in_skippable_prefix_is_still_a_comment_line() {
+        use tokei::testing::annotate_fixture;
+
+        // The first `"`/`/*` in the whole text (the `"q"` on line 3) is what decides where
+        // `annotate_from_slice`'s parallel "skippable prefix" ends; everything before it,
+        // including this trailing `// note`, is classified by that separate fast path rather
+        // than `annotate_lines`, so it needs the same any-comments-anywhere-in-the-line check.
+        let source = "let x = 1; // note\nlet y = 2;\nlet z = \"q\";\n";
+        let codes = "CXX";
+
+        annotate_fixture(LanguageType::Rust, source, codes, &Config::default()).unwrap();
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn annotate_fixture_counts_fenced_doc_code_as_code() {
+        use tokei::testing::annotate_fixture;
+
+        let source = "\
+/// fenced example:
+///
+/// ```
+/// let x = 1;
+/// ```
+///
+/// done
+fn foo() {}
+";
+        let codes = "DDCXCDDX";
+        let config = Config {
+            count_doc_code_as_code: Some(true),
+            ..Config::default()
+        };
+
+        annotate_fixture(LanguageType::Rust, source, codes, &config).unwrap();
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn annotate_fixture_unterminated_fence_stays_code_at_eof() {
+        use tokei::testing::annotate_fixture;
+
+        let source = "\
+/// ```
+/// still code at eof
+";
+        let codes = "CX";
+        let config = Config {
+            count_doc_code_as_code: Some(true),
+            ..Config::default()
+        };
+
+        annotate_fixture(LanguageType::Rust, source, codes, &config).unwrap();
+    }
+
+    #[test]
+    fn annotate_spans_on_indented_mixed_line() {
+        let line = "    let x = 5; // note";
+        let source = format!("{}\n", line);
+        let config = Config::default();
+
+        let spans = LanguageType::Rust.annotate_spans_from_slice(&source, &config);
+        let comment_start = line.find("//").unwrap();
+
+        // Spans are byte offsets within the *raw* (untrimmed) line, so they must cover it
+        // exactly, including the leading indent.
+        let line_spans = spans.get(&0).unwrap();
+        assert_eq!(line_spans[0].0, 0..comment_start);
+        assert_eq!(line_spans[0].1, LineType::Code);
+        assert_eq!(line_spans[1].0, comment_start..line.len());
+        assert_eq!(line_spans[1].1, LineType::Comment);
+    }
+
+    #[test]
+    fn annotate_spans_plain_string_literal_is_code_not_comment() {
+        let line = r#"let s = "hello world";"#;
+        let source = format!("{}\n", line);
+        let config = Config::default();
+
+        let spans = LanguageType::Rust.annotate_spans_from_slice(&source, &config);
+
+        // A plain string/char quote is not a comment, so the whole line stays one `Code` span
+        // (or several, but none of them `Comment`/`DocComment`).
+        let line_spans = spans.get(&0).unwrap();
+        assert!(line_spans
+            .iter()
+            .all(|(_, kind)| *kind == LineType::Code));
+    }
+
+    #[test]
+    fn annotate_ranges_merges_runs_and_drops_short_ones() {
+        let source = "\
+// line1
+// line2
+
+// line3
+code1
+code2
+";
+        let config = Config::default();
+
+        let ranges = LanguageType::Rust.annotate_ranges_from_slice(source, &config, Some(2));
+
+        assert_eq!(
+            ranges,
+            vec![(LineType::Comment, 0..2), (LineType::Code, 4..6)]
+        );
+    }
+
+    #[test]
+    fn annotate_parallel_matches_sequential() {
+        // `annotate_parallel_from_slice` always chunks by `rayon::current_num_threads()`, which
+        // this test can't pin, so it isn't a reliable way to exercise the chunk-boundary
+        // reconciliation path (on a single-core runner `line_aligned_chunks` returns one chunk
+        // and this falls straight back to `annotate_from_slice`). It still guards the common
+        // case end-to-end; the reconciliation path itself has a deterministic, chunk-count-pinned
+        // regression test alongside `line_aligned_chunks` in `src/language/annotate_file.rs`.
+        let mut source = String::from("/*\n");
+        for i in 0..200 {
+            source.push_str(&format!("comment line {}\n", i));
+        }
+        source.push_str("*/\ncode();\n");
+
+        let config = Config::default();
+
+        let sequential = LanguageType::Rust.annotate_from_slice(&source, &config);
+        let parallel = LanguageType::Rust.annotate_parallel_from_slice(&source, &config);
+
+        assert_eq!(parallel, sequential);
+    }
 }